@@ -0,0 +1,108 @@
+//! OpenPGP smartcard (YubiKey, Nitrokey, etc.) support.
+//!
+//! This module is only compiled with the `openpgp-card` feature, keeping the
+//! PCSC dependency optional for users who only ever use on-disk key files.
+
+use card_backend_pcsc::PcscBackend;
+use openpgp_card::OpenPgp;
+use openpgp_card_sequoia::{state::Open, Card};
+use sequoia_openpgp as openpgp;
+use sequoia_openpgp::crypto::{Decryptor, SessionKey};
+use sequoia_openpgp::packet::key::{PublicParts, UnspecifiedRole};
+use sequoia_openpgp::packet::Key;
+use sequoia_openpgp::crypto::mpi;
+
+use crate::EXIT_KEY_ERROR;
+
+/// Opens the first connected OpenPGP card, or the one whose application
+/// identifier contains `ident` if given.
+pub fn open_card(ident: Option<&str>) -> Result<Card<Open>, (i32, String)> {
+    let backends = PcscBackend::cards(None)
+        .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to enumerate PCSC readers: {}", e)))?;
+
+    for backend in backends {
+        let backend = backend
+            .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to open card reader: {}", e)))?;
+        let mut card: Card<Open> = OpenPgp::new(backend)
+            .open()
+            .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to open OpenPGP card: {}", e)))?;
+
+        if let Some(ident) = ident {
+            let aid = card
+                .transaction()
+                .and_then(|mut tx| tx.application_identifier())
+                .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to read card identifier: {}", e)))?;
+            if !aid.ident().contains(ident) {
+                continue;
+            }
+        }
+
+        return Ok(card);
+    }
+
+    Err((
+        EXIT_KEY_ERROR,
+        match ident {
+            Some(ident) => format!("No OpenPGP card matching '{}' is present", ident),
+            None => "No OpenPGP smartcard is present".to_string(),
+        },
+    ))
+}
+
+/// Reads the cardholder's encryption-subkey public material, for use as an
+/// encryption recipient.
+pub fn recipient_key(card: &mut Card<Open>) -> Result<Key<PublicParts, UnspecifiedRole>, (i32, String)> {
+    let mut tx = card
+        .transaction()
+        .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to start card session: {}", e)))?;
+
+    tx.public_key(openpgp_card::KeyType::Decryption)
+        .map_err(|e| {
+            (
+                EXIT_KEY_ERROR,
+                format!("Card has no decryption key slot: {}", e),
+            )
+        })
+}
+
+/// A [`Decryptor`] backed by a card's on-card decryption key. The private
+/// key material never leaves the token; each [`decrypt`](Decryptor::decrypt)
+/// call sends the ciphertext to the card and returns its response.
+pub struct CardDecryptor<'a> {
+    card: &'a mut Card<Open>,
+    public: Key<PublicParts, UnspecifiedRole>,
+    pin: String,
+}
+
+impl<'a> CardDecryptor<'a> {
+    /// Builds a decryptor for `card`'s decryption key, authenticating with
+    /// `pin` on each decryption.
+    pub fn new(card: &'a mut Card<Open>, pin: String) -> Result<Self, (i32, String)> {
+        let public = {
+            let mut tx = card.transaction().map_err(|e| {
+                (EXIT_KEY_ERROR, format!("Failed to start card session: {}", e))
+            })?;
+            tx.public_key(openpgp_card::KeyType::Decryption).map_err(|e| {
+                (EXIT_KEY_ERROR, format!("Card has no decryption key slot: {}", e))
+            })?
+        };
+
+        Ok(CardDecryptor { card, public, pin })
+    }
+}
+
+impl<'a> Decryptor for CardDecryptor<'a> {
+    fn public(&self) -> &Key<PublicParts, UnspecifiedRole> {
+        &self.public
+    }
+
+    fn decrypt(
+        &mut self,
+        ciphertext: &mpi::Ciphertext,
+        plaintext_len: Option<usize>,
+    ) -> openpgp::Result<SessionKey> {
+        let mut tx = self.card.transaction()?;
+        tx.verify_user_pin(self.pin.as_bytes())?;
+        tx.decipher(openpgp_card::KeyType::Decryption, ciphertext, plaintext_len)
+    }
+}
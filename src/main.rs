@@ -1,20 +1,35 @@
+use anyhow::anyhow;
 use clap::Parser;
+use openpgp::cert::Cert;
+use openpgp::crypto::{Decryptor, KeyPair, SessionKey};
+use openpgp::packet::{PKESK, SKESK};
+use openpgp::parse::stream::{
+    DecryptionHelper, DecryptorBuilder, MessageLayer, MessageStructure, VerificationHelper,
+};
 use openpgp::parse::Parse;
 use openpgp::policy::StandardPolicy;
-use openpgp::serialize::stream::{Encryptor2, LiteralWriter, Message};
+use openpgp::serialize::stream::{Encryptor2, LiteralWriter, Message, Signer};
+use openpgp::types::SymmetricAlgorithm;
+use openpgp::{Fingerprint, KeyHandle};
 use sequoia_openpgp as openpgp;
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 use walkdir::WalkDir;
 
+#[cfg(feature = "openpgp-card")]
+mod card;
+mod shamir;
+mod threshold;
+
 // Exit codes
 const EXIT_SUCCESS: i32 = 0;
 const EXIT_INVALID_INPUT: i32 = 1;
 const EXIT_KEY_ERROR: i32 = 2;
 const EXIT_ENCRYPTION_ERROR: i32 = 3;
 const EXIT_IO_ERROR: i32 = 4;
+const EXIT_ABORTED_EXISTING: i32 = 5;
 
 #[derive(Parser, Debug)]
 #[command(name = "pgp-encrypt", about = "Encrypt files using PGP")]
@@ -27,9 +42,53 @@ struct Opt {
     #[arg(short, long, value_name = "OUTPUT_DIR")]
     output: PathBuf,
 
-    /// Public key file path
+    /// Public key file path (secret key file when --decrypt is set); not
+    /// needed when --card is used instead. May be repeated to name multiple
+    /// recipients, e.g. for --threshold encryption
     #[arg(short, long, value_name = "KEY_FILE")]
-    key: PathBuf,
+    key: Vec<PathBuf>,
+
+    /// Encrypt so that only K of the given --key recipients, cooperating,
+    /// can decrypt (Shamir secret sharing of the session key)
+    #[arg(long, value_name = "K")]
+    threshold: Option<u8>,
+
+    /// Decrypt `.pgp` files in the input folder instead of encrypting them
+    #[arg(long)]
+    decrypt: bool,
+
+    /// Secret key file of a signer; sign files before encrypting them
+    #[arg(long, value_name = "SECRET_KEY_FILE")]
+    signer: Option<PathBuf>,
+
+    /// With --decrypt, verify the message was signed by this certificate;
+    /// fails if no valid signature from it is found
+    #[arg(long, value_name = "CERT_FILE", requires = "decrypt")]
+    verify_signer: Option<PathBuf>,
+
+    /// With --signer, write a standalone `.sig` file instead of encrypting
+    #[arg(long, requires = "signer")]
+    detached: bool,
+
+    /// Always overwrite existing output files without prompting
+    #[arg(long, conflicts_with = "skip_existing")]
+    force: bool,
+
+    /// Never overwrite existing output files
+    #[arg(long)]
+    skip_existing: bool,
+
+    /// After encrypting, re-parse each output file and confirm its PKESK
+    /// packets name exactly the intended recipients; delete and fail the
+    /// file on any mismatch or parse error
+    #[arg(long)]
+    verify: bool,
+
+    /// Use an OpenPGP smartcard instead of --key; optionally matches a card
+    /// by (part of) its application identifier
+    #[cfg(feature = "openpgp-card")]
+    #[arg(long, value_name = "CARD_IDENT", num_args = 0..=1, default_missing_value = "")]
+    card: Option<String>,
 }
 
 fn run() -> Result<(), (i32, String)> {
@@ -66,33 +125,114 @@ fn run_with_args(args: &[&str]) -> Result<(), (i32, String)> {
         ));
     }
 
-    // Validate public key file
-    if !opt.key.exists() {
+    #[cfg(feature = "openpgp-card")]
+    let using_card = opt.card.is_some();
+    #[cfg(not(feature = "openpgp-card"))]
+    let using_card = false;
+
+    if using_card && !opt.key.is_empty() {
         return Err((
             EXIT_INVALID_INPUT,
-            format!("Public key file '{}' does not exist", opt.key.display()),
+            "--key and --card are mutually exclusive".to_string(),
         ));
     }
+    if !using_card {
+        if opt.key.is_empty() {
+            return Err((
+                EXIT_INVALID_INPUT,
+                "Either --key or --card must be given".to_string(),
+            ));
+        }
+        for key in &opt.key {
+            if !key.exists() {
+                return Err((
+                    EXIT_INVALID_INPUT,
+                    format!("Key file '{}' does not exist", key.display()),
+                ));
+            }
+        }
+    }
+    if let Some(threshold) = opt.threshold {
+        if using_card {
+            return Err((
+                EXIT_INVALID_INPUT,
+                "--threshold is not supported with --card".to_string(),
+            ));
+        }
+        if opt.signer.is_some() {
+            return Err((
+                EXIT_INVALID_INPUT,
+                "--signer is not supported with --threshold".to_string(),
+            ));
+        }
+        if threshold == 0 {
+            return Err((
+                EXIT_INVALID_INPUT,
+                "--threshold must be at least 1".to_string(),
+            ));
+        }
+        if opt.key.len() > 254 {
+            return Err((
+                EXIT_INVALID_INPUT,
+                format!(
+                    "--threshold supports at most 254 --key recipients, got {}",
+                    opt.key.len()
+                ),
+            ));
+        }
+        if (opt.key.len() as u8) < threshold {
+            return Err((
+                EXIT_INVALID_INPUT,
+                format!(
+                    "--threshold {} requires at least {} --key recipients, got {}",
+                    threshold,
+                    threshold,
+                    opt.key.len()
+                ),
+            ));
+        }
+    }
 
     // Create policy
     let policy = StandardPolicy::new();
 
-    // Read and parse public key
-    let key_data = fs::read(&opt.key)
-        .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read public key: {}", e)))?;
-    let cert = openpgp::Cert::from_bytes(&key_data)
-        .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid public key: {}", e)))?;
+    if opt.decrypt {
+        return decrypt_folder(&opt, &policy);
+    }
 
-    // Get encryption-capable key
-    let recipients = cert
-        .keys()
-        .with_policy(&policy, None)
-        .supported()
-        .alive()
-        .revoked(false)
-        .for_transport_encryption()
-        .map(|k| k.key().clone())
-        .collect::<Vec<_>>();
+    if let Some(threshold) = opt.threshold {
+        return threshold_encrypt_folder(&opt, &policy, threshold);
+    }
+
+    // Get encryption-capable key, either from a public key file or a card
+    let recipients = if using_card {
+        #[cfg(feature = "openpgp-card")]
+        {
+            let mut card_handle = card::open_card(opt.card.as_deref().filter(|s| !s.is_empty()))?;
+            vec![card::recipient_key(&mut card_handle)?]
+        }
+        #[cfg(not(feature = "openpgp-card"))]
+        unreachable!("using_card is always false without the openpgp-card feature")
+    } else {
+        let mut recipients = Vec::new();
+        for key_path in &opt.key {
+            let key_data = fs::read(key_path)
+                .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read public key: {}", e)))?;
+            let cert = openpgp::Cert::from_bytes(&key_data)
+                .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid public key: {}", e)))?;
+
+            recipients.extend(
+                cert.keys()
+                    .with_policy(&policy, None)
+                    .supported()
+                    .alive()
+                    .revoked(false)
+                    .for_transport_encryption()
+                    .map(|k| k.key().clone()),
+            );
+        }
+        recipients
+    };
 
     if recipients.is_empty() {
         return Err((
@@ -101,7 +241,36 @@ fn run_with_args(args: &[&str]) -> Result<(), (i32, String)> {
         ));
     }
 
+    // Load the signer, if one was given
+    let signer_keypair = match &opt.signer {
+        Some(signer_path) => {
+            let signer_data = fs::read(signer_path).map_err(|e| {
+                (EXIT_IO_ERROR, format!("Failed to read signer key: {}", e))
+            })?;
+            let signer_cert = openpgp::Cert::from_bytes(&signer_data)
+                .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid signer key: {}", e)))?;
+
+            let password = if needs_passphrase(&signer_cert) {
+                let passphrase = rpassword::prompt_password("Enter passphrase for signer key: ")
+                    .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read passphrase: {}", e)))?;
+                Some(openpgp::crypto::Password::from(passphrase))
+            } else {
+                None
+            };
+
+            let pair = signing_keypair(&signer_cert, &policy, password.as_ref()).ok_or_else(|| {
+                (
+                    EXIT_KEY_ERROR,
+                    "No valid signing key found in the signer certificate".to_string(),
+                )
+            })?;
+            Some(pair)
+        }
+        None => None,
+    };
+
     // Process all files in the input folder
+    let mut any_skipped_declined = false;
     for entry in WalkDir::new(&opt.folder)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -122,7 +291,42 @@ fn run_with_args(args: &[&str]) -> Result<(), (i32, String)> {
             )
         })?;
 
-        // Create encrypted data
+        // With --detached, only produce a standalone signature; do not encrypt
+        if opt.detached {
+            let signing_pair = signer_keypair
+                .as_ref()
+                .expect("--detached requires --signer, enforced by clap");
+
+            let mut signature_data = Vec::new();
+            {
+                let message = Message::new(&mut signature_data);
+                let mut signer = Signer::new(message, signing_pair.clone())
+                    .detached()
+                    .build()
+                    .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to create signer: {}", e)))?;
+                signer.write_all(&input_data).map_err(|e| {
+                    (EXIT_ENCRYPTION_ERROR, format!("Failed to write data: {}", e))
+                })?;
+                signer.finalize().map_err(|e| {
+                    (
+                        EXIT_ENCRYPTION_ERROR,
+                        format!("Failed to finalize signature: {}", e),
+                    )
+                })?;
+            }
+
+            let relative_path = file_path.strip_prefix(&opt.folder).unwrap_or(file_path);
+            let output_path = append_extension(&opt.output.join(relative_path), "sig");
+
+            if write_output(&output_path, &signature_data, &opt)? == WriteOutcome::SkippedDeclined
+            {
+                any_skipped_declined = true;
+            }
+
+            continue;
+        }
+
+        // Create encrypted data, signing it first if a signer was given
         let mut encrypted_data = Vec::new();
         {
             let message = Message::new(&mut encrypted_data);
@@ -134,12 +338,27 @@ fn run_with_args(args: &[&str]) -> Result<(), (i32, String)> {
                         format!("Failed to create encryptor: {}", e),
                     )
                 })?;
-            let mut literal_writer = LiteralWriter::new(encryptor).build().map_err(|e| {
-                (
-                    EXIT_ENCRYPTION_ERROR,
-                    format!("Failed to create writer: {}", e),
-                )
-            })?;
+            let mut literal_writer = match &signer_keypair {
+                Some(signing_pair) => {
+                    let signer = Signer::new(encryptor, signing_pair.clone())
+                        .build()
+                        .map_err(|e| {
+                            (EXIT_ENCRYPTION_ERROR, format!("Failed to create signer: {}", e))
+                        })?;
+                    LiteralWriter::new(signer).build().map_err(|e| {
+                        (
+                            EXIT_ENCRYPTION_ERROR,
+                            format!("Failed to create writer: {}", e),
+                        )
+                    })?
+                }
+                None => LiteralWriter::new(encryptor).build().map_err(|e| {
+                    (
+                        EXIT_ENCRYPTION_ERROR,
+                        format!("Failed to create writer: {}", e),
+                    )
+                })?,
+            };
             literal_writer.write_all(&input_data).map_err(|e| {
                 (
                     EXIT_ENCRYPTION_ERROR,
@@ -158,32 +377,645 @@ fn run_with_args(args: &[&str]) -> Result<(), (i32, String)> {
         let relative_path = file_path.strip_prefix(&opt.folder).unwrap_or(file_path);
         let output_path = opt.output.join(relative_path).with_extension("pgp");
 
-        // Ensure output directories exist
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| {
+        // Write encrypted file to output folder, respecting overwrite policy
+        let outcome = write_output(&output_path, &encrypted_data, &opt)?;
+        match outcome {
+            WriteOutcome::SkippedDeclined => any_skipped_declined = true,
+            WriteOutcome::Written if opt.verify => {
+                let expected: Vec<openpgp::KeyID> =
+                    recipients.iter().map(|k| k.keyid()).collect();
+                if let Err(e) = verify_encryption_recipients(&encrypted_data, &expected) {
+                    let _ = fs::remove_file(&output_path);
+                    return Err(e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if any_skipped_declined {
+        return Err((
+            EXIT_ABORTED_EXISTING,
+            "Aborted: one or more output files already existed and were not overwritten"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns a usable [`KeyPair`] for the first signing-capable key in `cert`,
+/// decrypting its secret material with `password` if it is protected.
+fn signing_keypair(
+    cert: &Cert,
+    policy: &StandardPolicy,
+    password: Option<&openpgp::crypto::Password>,
+) -> Option<KeyPair> {
+    use openpgp::packet::key::SecretKeyMaterial;
+
+    cert.keys()
+        .with_policy(policy, None)
+        .supported()
+        .alive()
+        .revoked(false)
+        .for_signing()
+        .secret()
+        .find_map(|ka| {
+            let key = ka.key().clone();
+            let key = match key.optional_secret() {
+                Some(SecretKeyMaterial::Encrypted(_)) => {
+                    let password = password?;
+                    key.decrypt_secret(password).ok()?
+                }
+                _ => key,
+            };
+            key.into_keypair().ok()
+        })
+}
+
+/// Appends `.{ext}` to `path`'s file name without otherwise altering it, e.g.
+/// `report.txt` becomes `report.txt.sig`.
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Whether [`write_output`] wrote its data or left an existing file in place.
+#[derive(Debug, PartialEq, Eq)]
+enum WriteOutcome {
+    Written,
+    SkippedByFlag,
+    SkippedDeclined,
+}
+
+/// Writes `data` to `path`, honoring `opt.force` / `opt.skip_existing` and,
+/// for interactive sessions, prompting before clobbering an existing file.
+fn write_output(path: &Path, data: &[u8], opt: &Opt) -> Result<WriteOutcome, (i32, String)> {
+    if path.exists() && !opt.force {
+        if opt.skip_existing {
+            eprintln!("Skipping existing file '{}'", path.display());
+            return Ok(WriteOutcome::SkippedByFlag);
+        }
+
+        let overwrite = std::io::stdin().is_terminal() && confirm_overwrite(path)?;
+        if !overwrite {
+            eprintln!(
+                "Skipping existing file '{}' (use --force to overwrite)",
+                path.display()
+            );
+            return Ok(WriteOutcome::SkippedDeclined);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            (
+                EXIT_IO_ERROR,
+                format!("Failed to create output directories: {}", e),
+            )
+        })?;
+    }
+
+    fs::write(path, data).map_err(|e| {
+        (
+            EXIT_IO_ERROR,
+            format!("Failed to write {}: {}", path.display(), e),
+        )
+    })?;
+
+    Ok(WriteOutcome::Written)
+}
+
+/// Prompts on stdin whether to overwrite the existing file at `path`.
+fn confirm_overwrite(path: &Path) -> Result<bool, (i32, String)> {
+    print!("File '{}' already exists. Overwrite? [y/N] ", path.display());
+    std::io::stdout()
+        .flush()
+        .map_err(|e| (EXIT_IO_ERROR, format!("Failed to write prompt: {}", e)))?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read answer: {}", e)))?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Re-parses `data` as an OpenPGP message and confirms its PKESK packets
+/// name exactly `expected`, guarding `--verify` against silently shipping a
+/// corrupt or mis-targeted ciphertext.
+fn verify_encryption_recipients(
+    data: &[u8],
+    expected: &[openpgp::KeyID],
+) -> Result<(), (i32, String)> {
+    let pile = openpgp::PacketPile::from_bytes(data).map_err(|e| {
+        (
+            EXIT_ENCRYPTION_ERROR,
+            format!("Self-verification failed to parse output: {}", e),
+        )
+    })?;
+
+    let found: std::collections::HashSet<openpgp::KeyID> = pile
+        .descendants()
+        .filter_map(|packet| match packet {
+            openpgp::Packet::PKESK(pkesk) => Some(pkesk.recipient().clone()),
+            _ => None,
+        })
+        .collect();
+    let expected: std::collections::HashSet<openpgp::KeyID> = expected.iter().cloned().collect();
+
+    if found != expected {
+        return Err((
+            EXIT_ENCRYPTION_ERROR,
+            "Self-verification failed: encrypted output's recipients do not match the intended recipients"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Re-parses a threshold container's per-share ciphertexts and confirms each
+/// names its claimed recipient and that, together, they name exactly
+/// `expected` — the threshold-container counterpart of
+/// [`verify_encryption_recipients`].
+fn verify_threshold_recipients(
+    container: &[u8],
+    expected: &[openpgp::KeyID],
+) -> Result<(), (i32, String)> {
+    let parsed = threshold::parse(container)?;
+
+    let mut found = std::collections::HashSet::new();
+    for (keyid, _x, ciphertext) in &parsed.shares {
+        let share_pile = openpgp::PacketPile::from_bytes(ciphertext).map_err(|e| {
+            (
+                EXIT_ENCRYPTION_ERROR,
+                format!("Self-verification failed to parse share: {}", e),
+            )
+        })?;
+        let share_recipients: std::collections::HashSet<openpgp::KeyID> = share_pile
+            .descendants()
+            .filter_map(|packet| match packet {
+                openpgp::Packet::PKESK(pkesk) => Some(pkesk.recipient().clone()),
+                _ => None,
+            })
+            .collect();
+        if !share_recipients.contains(keyid) {
+            return Err((
+                EXIT_ENCRYPTION_ERROR,
+                "Self-verification failed: a threshold share's ciphertext does not name its claimed recipient"
+                    .to_string(),
+            ));
+        }
+        found.insert(keyid.clone());
+    }
+
+    let expected: std::collections::HashSet<openpgp::KeyID> = expected.iter().cloned().collect();
+    if found != expected {
+        return Err((
+            EXIT_ENCRYPTION_ERROR,
+            "Self-verification failed: threshold container's recipients do not match the intended recipients"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Helper for [`DecryptorBuilder`] that resolves session keys against a set
+/// of decryptors (software key pairs and, with the `openpgp-card` feature,
+/// card-backed keys) and, when `verify_signer_cert` is set, checks message
+/// signatures against it.
+struct Helper {
+    decryptors: Vec<Box<dyn Decryptor>>,
+    /// Session key recovered out-of-band (e.g. by reconstructing a
+    /// threshold-split key); when set, it is handed to the caller directly
+    /// instead of matching PKESKs against `decryptors`.
+    explicit_session_key: Option<(SymmetricAlgorithm, SessionKey)>,
+    /// Certificate to check message signatures against, set from
+    /// `--verify-signer`. `None` means signatures are not checked.
+    verify_signer_cert: Option<Cert>,
+}
+
+/// Builds usable [`KeyPair`]s for every encryption-capable secret key in
+/// `cert`, decrypting passphrase-protected key material with `password` if
+/// one is supplied.
+fn encryption_keypairs(cert: &Cert, password: Option<&openpgp::crypto::Password>) -> Vec<KeyPair> {
+    use openpgp::packet::key::SecretKeyMaterial;
+
+    cert.keys()
+        .secret()
+        .filter(|ka| ka.for_storage_encryption() || ka.for_transport_encryption())
+        .filter_map(|ka| {
+            let key = ka.key().clone();
+            let key = match key.optional_secret() {
+                Some(SecretKeyMaterial::Encrypted(_)) => {
+                    let password = password?;
+                    key.decrypt_secret(password).ok()?
+                }
+                _ => key,
+            };
+            key.into_keypair().ok()
+        })
+        .collect()
+}
+
+impl VerificationHelper for Helper {
+    fn get_certs(&mut self, _ids: &[KeyHandle]) -> openpgp::Result<Vec<Cert>> {
+        Ok(self.verify_signer_cert.clone().into_iter().collect())
+    }
+
+    fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
+        // Without --verify-signer, this tool does not check signatures.
+        if self.verify_signer_cert.is_none() {
+            return Ok(());
+        }
+
+        for layer in structure.into_iter() {
+            if let MessageLayer::SignatureGroup { results } = layer {
+                if results.iter().any(Result::is_ok) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "No valid signature from the --verify-signer certificate was found"
+        ))
+    }
+}
+
+impl DecryptionHelper for Helper {
+    fn decrypt<D>(
+        &mut self,
+        pkesks: &[PKESK],
+        _skesks: &[SKESK],
+        sym_algo: Option<SymmetricAlgorithm>,
+        mut decrypt: D,
+    ) -> openpgp::Result<Option<Fingerprint>>
+    where
+        D: FnMut(SymmetricAlgorithm, &SessionKey) -> bool,
+    {
+        if let Some((algo, session_key)) = self.explicit_session_key.take() {
+            return if decrypt(algo, &session_key) {
+                Ok(None)
+            } else {
+                Err(anyhow!("Reconstructed session key did not decrypt the message"))
+            };
+        }
+
+        // First, try to match each PKESK against a known key ID. This covers
+        // the common case where the message was not encrypted with
+        // throw-keyids.
+        for pkesk in pkesks {
+            if let Some(decryptor) = self
+                .decryptors
+                .iter_mut()
+                .find(|d| pkesk.recipient() == &d.public().keyid())
+            {
+                if let Some((algo, session_key)) = pkesk.decrypt(decryptor.as_mut(), sym_algo) {
+                    if decrypt(algo, &session_key) {
+                        return Ok(Some(decryptor.public().fingerprint()));
+                    }
+                }
+            }
+        }
+
+        // Fall back to trial decryption against every secret key we have,
+        // to support messages encrypted with throw-keyids.
+        for decryptor in self.decryptors.iter_mut() {
+            for pkesk in pkesks {
+                if let Some((algo, session_key)) = pkesk.decrypt(decryptor.as_mut(), sym_algo) {
+                    if decrypt(algo, &session_key) {
+                        return Ok(Some(decryptor.public().fingerprint()));
+                    }
+                }
+            }
+        }
+
+        Err(anyhow!("No matching secret key found to decrypt the session key"))
+    }
+}
+
+/// Encrypts every file under `opt.folder` so that any `threshold` of
+/// `opt.key`'s certificates, cooperating, can recover it. See
+/// [`crate::threshold`] for the on-disk format.
+fn threshold_encrypt_folder(
+    opt: &Opt,
+    policy: &StandardPolicy,
+    threshold: u8,
+) -> Result<(), (i32, String)> {
+    let mut recipients = Vec::with_capacity(opt.key.len());
+    for key_path in &opt.key {
+        let key_data = fs::read(key_path)
+            .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read public key: {}", e)))?;
+        let cert = openpgp::Cert::from_bytes(&key_data)
+            .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid public key: {}", e)))?;
+
+        let key = cert
+            .keys()
+            .with_policy(policy, None)
+            .supported()
+            .alive()
+            .revoked(false)
+            .for_transport_encryption()
+            .map(|k| k.key().clone())
+            .next()
+            .ok_or_else(|| {
                 (
-                    EXIT_IO_ERROR,
-                    format!("Failed to create output directories: {}", e),
+                    EXIT_KEY_ERROR,
+                    format!(
+                        "No valid encryption key found in '{}'",
+                        key_path.display()
+                    ),
                 )
             })?;
+        recipients.push(key);
+    }
+
+    let mut any_skipped_declined = false;
+    for entry in WalkDir::new(&opt.folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let file_path = entry.path();
+
+        if file_path.extension().is_some_and(|ext| ext == "pgp") {
+            continue;
         }
 
-        // Write encrypted file to output folder
-        fs::write(&output_path, &encrypted_data).map_err(|e| {
+        let input_data = fs::read(file_path).map_err(|e| {
             (
                 EXIT_IO_ERROR,
-                format!(
-                    "Failed to write encrypted file {}: {}",
-                    output_path.display(),
-                    e
-                ),
+                format!("Failed to read {}: {}", file_path.display(), e),
             )
         })?;
+
+        let mut container = Vec::new();
+        threshold::encrypt(&mut container, &input_data, &recipients, threshold, policy)?;
+
+        let relative_path = file_path.strip_prefix(&opt.folder).unwrap_or(file_path);
+        let output_path = opt.output.join(relative_path).with_extension("pgp");
+
+        let outcome = write_output(&output_path, &container, opt)?;
+        match outcome {
+            WriteOutcome::SkippedDeclined => any_skipped_declined = true,
+            WriteOutcome::Written if opt.verify => {
+                let expected: Vec<openpgp::KeyID> =
+                    recipients.iter().map(|k| k.keyid()).collect();
+                if let Err(e) = verify_threshold_recipients(&container, &expected) {
+                    let _ = fs::remove_file(&output_path);
+                    return Err(e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if any_skipped_declined {
+        return Err((
+            EXIT_ABORTED_EXISTING,
+            "Aborted: one or more output files already existed and were not overwritten"
+                .to_string(),
+        ));
     }
 
     Ok(())
 }
 
+/// Recovers the plaintext body of a threshold-encrypted container by
+/// decrypting as many Shamir shares as possible with the secret keys in
+/// `certs`, reconstructing the session key once `container.threshold` of
+/// them are recovered, and decrypting the body with it.
+fn decrypt_threshold_container(
+    data: &[u8],
+    certs: &[(Cert, Option<openpgp::crypto::Password>)],
+    policy: &StandardPolicy,
+) -> Result<Vec<u8>, (i32, String)> {
+    let container = threshold::parse(data)?;
+
+    let mut recovered = Vec::new();
+    for (keyid, x, ciphertext) in &container.shares {
+        if recovered.len() >= container.threshold as usize {
+            break;
+        }
+
+        let pair = certs.iter().find_map(|(cert, password)| {
+            encryption_keypairs(cert, password.as_ref())
+                .into_iter()
+                .find(|kp| kp.public().keyid() == *keyid)
+        });
+        let Some(pair) = pair else { continue };
+
+        if let Ok(share) = threshold::decrypt_share(ciphertext, pair, policy) {
+            recovered.push(shamir::Share { x: *x, y: share });
+        }
+    }
+
+    if recovered.len() < container.threshold as usize {
+        return Err((
+            EXIT_KEY_ERROR,
+            format!(
+                "Only recovered {} of {} required shares",
+                recovered.len(),
+                container.threshold
+            ),
+        ));
+    }
+
+    let session_key = SessionKey::from(shamir::reconstruct(&recovered));
+
+    let mut helper = Helper {
+        decryptors: Vec::new(),
+        explicit_session_key: Some((container.sym_algo, session_key)),
+        // Threshold encryption never signs its body (see the --signer /
+        // --threshold rejection above), so there is nothing to check.
+        verify_signer_cert: None,
+    };
+    let mut reader = DecryptorBuilder::from_bytes(container.body)
+        .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to parse container body: {}", e)))?
+        .with_policy(policy, None, &mut helper)
+        .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to decrypt container body: {}", e)))?;
+
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to read decrypted data: {}", e)))?;
+    Ok(plaintext)
+}
+
+/// Decrypts every `.pgp` file under `opt.folder`, writing the recovered
+/// plaintext into the mirrored path under `opt.output`. Secret key material
+/// comes from `opt.key`, or from `opt.card` with the `openpgp-card` feature.
+fn decrypt_folder(opt: &Opt, policy: &StandardPolicy) -> Result<(), (i32, String)> {
+    #[cfg(feature = "openpgp-card")]
+    let using_card = opt.card.is_some();
+    #[cfg(not(feature = "openpgp-card"))]
+    let using_card = false;
+
+    let mut certs = Vec::with_capacity(opt.key.len());
+    for key_path in &opt.key {
+        let key_data = fs::read(key_path)
+            .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read secret key: {}", e)))?;
+        let cert = openpgp::Cert::from_bytes(&key_data)
+            .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid secret key: {}", e)))?;
+
+        let password = if needs_passphrase(&cert) {
+            let passphrase = rpassword::prompt_password(&format!(
+                "Enter passphrase for {}: ",
+                key_path.display()
+            ))
+            .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read passphrase: {}", e)))?;
+            Some(openpgp::crypto::Password::from(passphrase))
+        } else {
+            None
+        };
+
+        certs.push((cert, password));
+    }
+
+    #[cfg(feature = "openpgp-card")]
+    let mut card_handle = if using_card {
+        Some(card::open_card(
+            opt.card.as_deref().filter(|s| !s.is_empty()),
+        )?)
+    } else {
+        None
+    };
+
+    let mut decryptors: Vec<Box<dyn Decryptor>> = Vec::new();
+    for (cert, password) in &certs {
+        decryptors.extend(
+            encryption_keypairs(cert, password.as_ref())
+                .into_iter()
+                .map(|kp| Box::new(kp) as Box<dyn Decryptor>),
+        );
+    }
+    #[cfg(feature = "openpgp-card")]
+    if let Some(card_handle) = card_handle.as_mut() {
+        let pin = rpassword::prompt_password("Enter card PIN: ")
+            .map_err(|e| (EXIT_IO_ERROR, format!("Failed to read PIN: {}", e)))?;
+        decryptors.push(Box::new(card::CardDecryptor::new(card_handle, pin)?));
+    }
+
+    if decryptors.is_empty() {
+        return Err((
+            EXIT_KEY_ERROR,
+            "No usable decryption key found".to_string(),
+        ));
+    }
+
+    let verify_signer_cert = match &opt.verify_signer {
+        Some(path) => {
+            let data = fs::read(path).map_err(|e| {
+                (EXIT_IO_ERROR, format!("Failed to read verify-signer cert: {}", e))
+            })?;
+            Some(
+                openpgp::Cert::from_bytes(&data)
+                    .map_err(|e| (EXIT_KEY_ERROR, format!("Invalid verify-signer cert: {}", e)))?,
+            )
+        }
+        None => None,
+    };
+
+    let mut any_skipped_declined = false;
+    for entry in WalkDir::new(&opt.folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "pgp"))
+    {
+        let file_path = entry.path();
+
+        let encrypted_data = fs::read(file_path).map_err(|e| {
+            (
+                EXIT_IO_ERROR,
+                format!("Failed to read {}: {}", file_path.display(), e),
+            )
+        })?;
+
+        let plaintext = if threshold::is_container(&encrypted_data) {
+            if verify_signer_cert.is_some() {
+                return Err((
+                    EXIT_ENCRYPTION_ERROR,
+                    format!(
+                        "Failed to decrypt {}: threshold-encrypted files carry no signature to check against --verify-signer",
+                        file_path.display()
+                    ),
+                ));
+            }
+            decrypt_threshold_container(&encrypted_data, &certs, policy).map_err(|(code, msg)| {
+                (code, format!("Failed to decrypt {}: {}", file_path.display(), msg))
+            })?
+        } else {
+            let mut helper = Helper {
+                decryptors: std::mem::take(&mut decryptors),
+                explicit_session_key: None,
+                verify_signer_cert: verify_signer_cert.clone(),
+            };
+            let mut reader = DecryptorBuilder::from_bytes(&encrypted_data)
+                .map_err(|e| {
+                    (
+                        EXIT_ENCRYPTION_ERROR,
+                        format!("Failed to parse {}: {}", file_path.display(), e),
+                    )
+                })?
+                .with_policy(policy, None, &mut helper)
+                .map_err(|e| {
+                    (
+                        EXIT_ENCRYPTION_ERROR,
+                        format!("Failed to decrypt {}: {}", file_path.display(), e),
+                    )
+                })?;
+
+            let mut plaintext = Vec::new();
+            reader.read_to_end(&mut plaintext).map_err(|e| {
+                (
+                    EXIT_ENCRYPTION_ERROR,
+                    format!(
+                        "Failed to read decrypted data from {}: {}",
+                        file_path.display(),
+                        e
+                    ),
+                )
+            })?;
+            drop(reader);
+
+            decryptors = helper.decryptors;
+            plaintext
+        };
+
+        let relative_path = file_path.strip_prefix(&opt.folder).unwrap_or(file_path);
+        let output_path = opt.output.join(relative_path).with_extension("");
+
+        if write_output(&output_path, &plaintext, opt)? == WriteOutcome::SkippedDeclined {
+            any_skipped_declined = true;
+        }
+    }
+
+    if any_skipped_declined {
+        return Err((
+            EXIT_ABORTED_EXISTING,
+            "Aborted: one or more output files already existed and were not overwritten"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns true if any secret key in `cert` is passphrase-protected.
+fn needs_passphrase(cert: &Cert) -> bool {
+    use openpgp::packet::key::SecretKeyMaterial;
+
+    cert.keys().secret().any(|ka| {
+        matches!(ka.key().optional_secret(), Some(SecretKeyMaterial::Encrypted(_)))
+    })
+}
+
 fn main() {
     match run() {
         Ok(_) => process::exit(EXIT_SUCCESS),
@@ -223,7 +1055,17 @@ mod tests {
 
     // Helper function to run tests with args
     fn run_test_with_args(folder: &Path, output: &Path, key: &Path) -> Result<(), (i32, String)> {
-        let args = vec![
+        run_test_with_extra_args(folder, output, key, &[])
+    }
+
+    // Helper function to run tests with additional flags (e.g. --force)
+    fn run_test_with_extra_args(
+        folder: &Path,
+        output: &Path,
+        key: &Path,
+        extra: &[&str],
+    ) -> Result<(), (i32, String)> {
+        let mut args = vec![
             "pgp-encrypt",
             "--folder",
             folder.to_str().unwrap(),
@@ -232,6 +1074,7 @@ mod tests {
             "--key",
             key.to_str().unwrap(),
         ];
+        args.extend_from_slice(extra);
         run_with_args(&args)
     }
 
@@ -265,4 +1108,540 @@ mod tests {
         assert!(run_test_with_args(input_dir.path(), output_dir.path(), &key_path).is_ok());
         assert!(fs::read_dir(output_dir.path()).unwrap().count() == 0);
     }
+
+    #[test]
+    fn test_overwrite_declined_without_force_aborts() {
+        let (input_dir, output_dir, key_path) = setup_test_environment();
+        fs::write(input_dir.path().join("secret.txt"), b"hello world").unwrap();
+
+        assert!(run_test_with_args(input_dir.path(), output_dir.path(), &key_path).is_ok());
+
+        // Stdin isn't a TTY under `cargo test`, so the second run can't
+        // prompt and must skip the existing file and report the abort.
+        let result = run_test_with_args(input_dir.path(), output_dir.path(), &key_path);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().0, EXIT_ABORTED_EXISTING);
+    }
+
+    #[test]
+    fn test_overwrite_with_force_succeeds() {
+        let (input_dir, output_dir, key_path) = setup_test_environment();
+        fs::write(input_dir.path().join("secret.txt"), b"hello world").unwrap();
+
+        assert!(run_test_with_args(input_dir.path(), output_dir.path(), &key_path).is_ok());
+        assert!(run_test_with_extra_args(
+            input_dir.path(),
+            output_dir.path(),
+            &key_path,
+            &["--force"]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_skip_existing_leaves_file_untouched() {
+        let (input_dir, output_dir, key_path) = setup_test_environment();
+        fs::write(input_dir.path().join("secret.txt"), b"hello world").unwrap();
+
+        assert!(run_test_with_args(input_dir.path(), output_dir.path(), &key_path).is_ok());
+        let output_path = output_dir.path().join("secret.pgp");
+        let original = fs::read(&output_path).unwrap();
+
+        assert!(run_test_with_extra_args(
+            input_dir.path(),
+            output_dir.path(),
+            &key_path,
+            &["--skip-existing"]
+        )
+        .is_ok());
+        assert_eq!(fs::read(&output_path).unwrap(), original);
+    }
+
+    // Writes a fresh secret-key-capable cert to `dir` and returns its path.
+    fn write_test_key(dir: &Path, name: &str) -> PathBuf {
+        let (cert, _) = CertBuilder::new()
+            .add_userid("test@example.com")
+            .add_transport_encryption_subkey()
+            .generate()
+            .unwrap();
+        let key_path = dir.join(name);
+        let mut key_file = File::create(&key_path).unwrap();
+        cert.as_tsk().armored().serialize(&mut key_file).unwrap();
+        key_path
+    }
+
+    // Like `write_test_key`, but the cert also has a signing-capable subkey,
+    // for the --signer / --detached / --verify-signer tests.
+    fn write_signing_test_key(dir: &Path, name: &str) -> PathBuf {
+        let (cert, _) = CertBuilder::new()
+            .add_userid("test@example.com")
+            .add_transport_encryption_subkey()
+            .add_signing_subkey()
+            .generate()
+            .unwrap();
+        let key_path = dir.join(name);
+        let mut key_file = File::create(&key_path).unwrap();
+        cert.as_tsk().armored().serialize(&mut key_file).unwrap();
+        key_path
+    }
+
+    #[test]
+    fn test_sign_then_encrypt_round_trip_with_verify_signer() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+        let decrypted_dir = tempdir().unwrap();
+        let plaintext_dir = tempdir().unwrap();
+
+        let key_path = write_signing_test_key(input_dir.path(), "signer.pgp");
+        fs::write(plaintext_dir.path().join("secret.txt"), b"signed and sealed").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+            "--signer",
+            key_path.to_str().unwrap(),
+        ])
+        .is_ok());
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--decrypt",
+            "--folder",
+            encrypted_dir.path().to_str().unwrap(),
+            "--output",
+            decrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+            "--verify-signer",
+            key_path.to_str().unwrap(),
+        ])
+        .is_ok());
+
+        let recovered = fs::read(decrypted_dir.path().join("secret.txt")).unwrap();
+        assert_eq!(recovered, b"signed and sealed");
+    }
+
+    #[test]
+    fn test_verify_signer_rejects_unrelated_certificate() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+        let decrypted_dir = tempdir().unwrap();
+        let plaintext_dir = tempdir().unwrap();
+
+        let key_path = write_signing_test_key(input_dir.path(), "signer.pgp");
+        let other_key_path = write_signing_test_key(input_dir.path(), "other.pgp");
+        fs::write(plaintext_dir.path().join("secret.txt"), b"signed and sealed").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+            "--signer",
+            key_path.to_str().unwrap(),
+        ])
+        .is_ok());
+
+        // `other_key_path` never signed this message, so verification must
+        // fail even though decryption itself would otherwise succeed.
+        let result = run_with_args(&[
+            "pgp-encrypt",
+            "--decrypt",
+            "--folder",
+            encrypted_dir.path().to_str().unwrap(),
+            "--output",
+            decrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+            "--verify-signer",
+            other_key_path.to_str().unwrap(),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().0, EXIT_ENCRYPTION_ERROR);
+    }
+
+    #[test]
+    fn test_detached_signature_verifies_against_original_file() {
+        let input_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        let key_path = write_signing_test_key(input_dir.path(), "signer.pgp");
+
+        fs::write(input_dir.path().join("secret.txt"), b"detached content").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            input_dir.path().to_str().unwrap(),
+            "--output",
+            output_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+            "--signer",
+            key_path.to_str().unwrap(),
+            "--detached",
+        ])
+        .is_ok());
+
+        let sig_path = output_dir.path().join("secret.txt.sig");
+        let sig_data = fs::read(&sig_path).unwrap();
+
+        let key_data = fs::read(&key_path).unwrap();
+        let cert = Cert::from_bytes(&key_data).unwrap();
+
+        struct DetachedVerifyHelper {
+            cert: Cert,
+        }
+        impl VerificationHelper for DetachedVerifyHelper {
+            fn get_certs(&mut self, _ids: &[KeyHandle]) -> openpgp::Result<Vec<Cert>> {
+                Ok(vec![self.cert.clone()])
+            }
+            fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
+                for layer in structure.into_iter() {
+                    if let MessageLayer::SignatureGroup { results } = layer {
+                        if results.iter().any(Result::is_ok) {
+                            return Ok(());
+                        }
+                    }
+                }
+                Err(anyhow!("no valid signature"))
+            }
+        }
+
+        let policy = StandardPolicy::new();
+        let mut helper = DetachedVerifyHelper { cert };
+        let mut verifier = openpgp::parse::stream::DetachedVerifierBuilder::from_bytes(&sig_data)
+            .unwrap()
+            .with_policy(&policy, None, &mut helper)
+            .unwrap();
+        verifier.verify_bytes(b"detached content").unwrap();
+    }
+
+    #[test]
+    fn test_encrypt_then_decrypt_round_trip() {
+        let (input_dir, encrypted_dir, _) = setup_test_environment();
+        let key_path = write_test_key(input_dir.path(), "secret_key.pgp");
+        let decrypted_dir = tempdir().unwrap();
+        fs::write(input_dir.path().join("secret.txt"), b"round trip").unwrap();
+
+        assert!(run_test_with_args(input_dir.path(), encrypted_dir.path(), &key_path).is_ok());
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--decrypt",
+            "--folder",
+            encrypted_dir.path().to_str().unwrap(),
+            "--output",
+            decrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_path.to_str().unwrap(),
+        ])
+        .is_ok());
+
+        let recovered = fs::read(decrypted_dir.path().join("secret.txt")).unwrap();
+        assert_eq!(recovered, b"round trip");
+    }
+
+    #[test]
+    fn test_decrypt_with_passphrase_protected_key() {
+        let (cert, _) = CertBuilder::new()
+            .add_userid("test@example.com")
+            .add_transport_encryption_subkey()
+            .set_password(Some(openpgp::crypto::Password::from("hunter2".to_string())))
+            .generate()
+            .unwrap();
+
+        let policy = StandardPolicy::new();
+        let recipients: Vec<_> = cert
+            .keys()
+            .with_policy(&policy, None)
+            .supported()
+            .alive()
+            .revoked(false)
+            .for_transport_encryption()
+            .map(|k| k.key().clone())
+            .collect();
+
+        let plaintext = b"hello passphrase";
+        let mut encrypted = Vec::new();
+        {
+            let message = Message::new(&mut encrypted);
+            let encryptor = Encryptor2::for_recipients(message, recipients.iter())
+                .build()
+                .unwrap();
+            let mut writer = LiteralWriter::new(encryptor).build().unwrap();
+            writer.write_all(plaintext).unwrap();
+            writer.finalize().unwrap();
+        }
+
+        // Without the passphrase, the encrypted secret key material can't
+        // be used.
+        assert!(encryption_keypairs(&cert, None).is_empty());
+
+        // With the correct passphrase, it decrypts and the resulting key
+        // pair recovers the plaintext.
+        let password = openpgp::crypto::Password::from("hunter2".to_string());
+        let pairs = encryption_keypairs(&cert, Some(&password));
+        assert_eq!(pairs.len(), 1);
+
+        let mut helper = Helper {
+            decryptors: pairs
+                .into_iter()
+                .map(|kp| Box::new(kp) as Box<dyn Decryptor>)
+                .collect(),
+            explicit_session_key: None,
+            verify_signer_cert: None,
+        };
+        let mut reader = DecryptorBuilder::from_bytes(&encrypted)
+            .unwrap()
+            .with_policy(&policy, None, &mut helper)
+            .unwrap();
+        let mut recovered = Vec::new();
+        reader.read_to_end(&mut recovered).unwrap();
+        assert_eq!(recovered, plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_falls_back_to_trial_decryption_for_throw_keyids() {
+        let (cert, _) = CertBuilder::new()
+            .add_userid("test@example.com")
+            .add_transport_encryption_subkey()
+            .generate()
+            .unwrap();
+
+        let pairs = encryption_keypairs(&cert, None);
+        assert_eq!(pairs.len(), 1);
+        let public_key = pairs[0].public().clone();
+
+        let plaintext = b"hidden recipient";
+        let mut encrypted = Vec::new();
+        {
+            let message = Message::new(&mut encrypted);
+            let recipient = openpgp::serialize::stream::Recipient::from(&public_key).hide();
+            let encryptor = Encryptor2::for_recipients(message, vec![recipient])
+                .build()
+                .unwrap();
+            let mut writer = LiteralWriter::new(encryptor).build().unwrap();
+            writer.write_all(plaintext).unwrap();
+            writer.finalize().unwrap();
+        }
+
+        let policy = StandardPolicy::new();
+        let mut helper = Helper {
+            decryptors: pairs
+                .into_iter()
+                .map(|kp| Box::new(kp) as Box<dyn Decryptor>)
+                .collect(),
+            explicit_session_key: None,
+            verify_signer_cert: None,
+        };
+        let mut reader = DecryptorBuilder::from_bytes(&encrypted)
+            .unwrap()
+            .with_policy(&policy, None, &mut helper)
+            .unwrap();
+        let mut recovered = Vec::new();
+        reader.read_to_end(&mut recovered).unwrap();
+        assert_eq!(recovered, plaintext);
+    }
+
+    #[test]
+    fn test_threshold_encrypt_decrypt_with_quorum() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+        let decrypted_dir = tempdir().unwrap();
+
+        let key_a = write_test_key(input_dir.path(), "a.pgp");
+        let key_b = write_test_key(input_dir.path(), "b.pgp");
+        let key_c = write_test_key(input_dir.path(), "c.pgp");
+
+        let plaintext_dir = tempdir().unwrap();
+        fs::write(plaintext_dir.path().join("secret.txt"), b"hello threshold").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+            "--key",
+            key_b.to_str().unwrap(),
+            "--key",
+            key_c.to_str().unwrap(),
+            "--threshold",
+            "2",
+        ])
+        .is_ok());
+
+        // Any 2 of the 3 recipients should be able to decrypt.
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--decrypt",
+            "--folder",
+            encrypted_dir.path().to_str().unwrap(),
+            "--output",
+            decrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+            "--key",
+            key_c.to_str().unwrap(),
+        ])
+        .is_ok());
+
+        let recovered = fs::read(decrypted_dir.path().join("secret.txt")).unwrap();
+        assert_eq!(recovered, b"hello threshold");
+    }
+
+    #[test]
+    fn test_threshold_decrypt_below_quorum_fails() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+        let decrypted_dir = tempdir().unwrap();
+
+        let key_a = write_test_key(input_dir.path(), "a.pgp");
+        let key_b = write_test_key(input_dir.path(), "b.pgp");
+        let key_c = write_test_key(input_dir.path(), "c.pgp");
+
+        let plaintext_dir = tempdir().unwrap();
+        fs::write(plaintext_dir.path().join("secret.txt"), b"hello threshold").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+            "--key",
+            key_b.to_str().unwrap(),
+            "--key",
+            key_c.to_str().unwrap(),
+            "--threshold",
+            "2",
+        ])
+        .is_ok());
+
+        // Only 1 of the 3 recipients is nowhere near enough.
+        let result = run_with_args(&[
+            "pgp-encrypt",
+            "--decrypt",
+            "--folder",
+            encrypted_dir.path().to_str().unwrap(),
+            "--output",
+            decrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().0, EXIT_KEY_ERROR);
+    }
+
+    #[test]
+    fn test_threshold_encrypt_with_verify_succeeds() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+
+        let key_a = write_test_key(input_dir.path(), "a.pgp");
+        let key_b = write_test_key(input_dir.path(), "b.pgp");
+        let key_c = write_test_key(input_dir.path(), "c.pgp");
+
+        let plaintext_dir = tempdir().unwrap();
+        fs::write(plaintext_dir.path().join("secret.txt"), b"hello threshold").unwrap();
+
+        assert!(run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+            "--key",
+            key_b.to_str().unwrap(),
+            "--key",
+            key_c.to_str().unwrap(),
+            "--threshold",
+            "2",
+            "--verify",
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn test_threshold_rejects_signer() {
+        let input_dir = tempdir().unwrap();
+        let encrypted_dir = tempdir().unwrap();
+
+        let key_a = write_test_key(input_dir.path(), "a.pgp");
+        let key_b = write_test_key(input_dir.path(), "b.pgp");
+        let signer = write_test_key(input_dir.path(), "signer.pgp");
+
+        let plaintext_dir = tempdir().unwrap();
+        fs::write(plaintext_dir.path().join("secret.txt"), b"hello threshold").unwrap();
+
+        let result = run_with_args(&[
+            "pgp-encrypt",
+            "--folder",
+            plaintext_dir.path().to_str().unwrap(),
+            "--output",
+            encrypted_dir.path().to_str().unwrap(),
+            "--key",
+            key_a.to_str().unwrap(),
+            "--key",
+            key_b.to_str().unwrap(),
+            "--threshold",
+            "2",
+            "--signer",
+            signer.to_str().unwrap(),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().0, EXIT_INVALID_INPUT);
+    }
+
+    #[test]
+    fn test_verify_accepts_well_formed_output() {
+        let (input_dir, output_dir, key_path) = setup_test_environment();
+        fs::write(input_dir.path().join("secret.txt"), b"hello world").unwrap();
+
+        assert!(run_test_with_extra_args(
+            input_dir.path(),
+            output_dir.path(),
+            &key_path,
+            &["--verify"]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_verify_detects_corrupted_output() {
+        let (input_dir, output_dir, key_path) = setup_test_environment();
+        fs::write(input_dir.path().join("secret.txt"), b"hello world").unwrap();
+
+        let key_data = fs::read(&key_path).unwrap();
+        let cert = Cert::from_bytes(&key_data).unwrap();
+        let recipient_keyid = cert
+            .keys()
+            .for_transport_encryption()
+            .next()
+            .unwrap()
+            .keyid();
+
+        assert!(
+            run_test_with_args(input_dir.path(), output_dir.path(), &key_path).is_ok()
+        );
+        let output_path = output_dir.path().join("secret.pgp");
+        let mut data = fs::read(&output_path).unwrap();
+        data[2] ^= 0xFF;
+
+        assert!(verify_encryption_recipients(&data, &[recipient_keyid]).is_err());
+    }
 }
@@ -0,0 +1,158 @@
+//! Shamir's Secret Sharing over GF(256), byte-wise.
+//!
+//! Each secret byte is the constant term of an independent random
+//! polynomial of degree `k - 1` over the Rijndael field (generator `0x03`,
+//! reduction polynomial `0x11B`); a share is that polynomial evaluated at a
+//! fixed, nonzero x-coordinate. Recombining any `k` shares recovers the
+//! secret via Lagrange interpolation at `x = 0`; fewer than `k` shares leak
+//! nothing, since every byte value is equally consistent with them.
+
+use sequoia_openpgp::crypto;
+
+/// One share of a split secret: the x-coordinate it was evaluated at, and
+/// the resulting byte string (same length as the original secret).
+#[derive(Debug, Clone)]
+pub struct Share {
+    pub x: u8,
+    pub y: Vec<u8>,
+}
+
+/// Splits `secret` into `n` shares such that any `threshold` of them
+/// reconstruct it. `x` coordinates are assigned `1..=n` (never `0`, which
+/// is reserved for the secret itself).
+pub fn split(secret: &[u8], threshold: u8, n: u8) -> sequoia_openpgp::Result<Vec<Share>> {
+    assert!(threshold >= 1, "threshold must be at least 1");
+    assert!(n >= threshold, "n must be at least the threshold");
+    assert!(n < 255, "at most 254 shares are supported (x=0 is reserved)");
+
+    let mut shares: Vec<Share> = (1..=n)
+        .map(|x| Share {
+            x,
+            y: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &secret_byte in secret {
+        coefficients[0] = secret_byte;
+        crypto::random(&mut coefficients[1..])?;
+
+        for share in shares.iter_mut() {
+            share.y.push(eval_poly(&coefficients, share.x));
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Reconstructs the original secret from at least `threshold` shares, via
+/// Lagrange interpolation at `x = 0`. The caller is responsible for only
+/// passing shares that actually belong to the same secret.
+pub fn reconstruct(shares: &[Share]) -> Vec<u8> {
+    let len = shares.first().map_or(0, |s| s.y.len());
+    let mut secret = vec![0u8; len];
+
+    for (byte_idx, secret_byte) in secret.iter_mut().enumerate() {
+        let mut acc = 0u8;
+        for (i, share_i) in shares.iter().enumerate() {
+            let mut numerator = 1u8;
+            let mut denominator = 1u8;
+            for (j, share_j) in shares.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                // Lagrange basis polynomial evaluated at x = 0. In GF(256),
+                // subtraction is XOR, so `0 - x_j == x_j`.
+                numerator = gf256_mul(numerator, share_j.x);
+                denominator = gf256_mul(denominator, share_i.x ^ share_j.x);
+            }
+            let weight = gf256_div(numerator, denominator);
+            acc ^= gf256_mul(share_i.y[byte_idx], weight);
+        }
+        *secret_byte = acc;
+    }
+
+    secret
+}
+
+/// Evaluates the polynomial with the given coefficients (constant term
+/// first) at `x`, using Horner's method in GF(256).
+fn eval_poly(coefficients: &[u8], x: u8) -> u8 {
+    let mut result = 0u8;
+    for &c in coefficients.iter().rev() {
+        result = gf256_mul(result, x) ^ c;
+    }
+    result
+}
+
+/// GF(256) multiplication using the Rijndael field (reduction polynomial
+/// `x^8 + x^4 + x^3 + x + 1`, i.e. `0x11B`).
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1B;
+        }
+        b >>= 1;
+    }
+    result
+}
+
+/// GF(256) multiplicative inverse via Fermat's little theorem: in a field
+/// of order 2^8, `a^254 == a^-1` for every nonzero `a`.
+fn gf256_inv(a: u8) -> u8 {
+    assert!(a != 0, "zero has no multiplicative inverse");
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = gf256_mul(result, base);
+        }
+        base = gf256_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn gf256_div(a: u8, b: u8) -> u8 {
+    gf256_mul(a, gf256_inv(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_reconstruct_round_trip() {
+        let secret = b"a session key, 32 bytes long!!!!";
+        let shares = split(secret, 3, 5).unwrap();
+
+        // Any 3-of-5 subset reconstructs the secret.
+        let subset = vec![shares[0].clone(), shares[2].clone(), shares[4].clone()];
+        assert_eq!(reconstruct(&subset), secret);
+
+        let subset = vec![shares[1].clone(), shares[2].clone(), shares[3].clone()];
+        assert_eq!(reconstruct(&subset), secret);
+    }
+
+    #[test]
+    fn fewer_than_threshold_does_not_reconstruct() {
+        let secret = b"top secret";
+        let shares = split(secret, 3, 5).unwrap();
+        let subset = vec![shares[0].clone(), shares[1].clone()];
+        assert_ne!(reconstruct(&subset), secret);
+    }
+
+    #[test]
+    fn gf256_inverse_round_trips() {
+        for a in 1..=255u8 {
+            assert_eq!(gf256_mul(a, gf256_inv(a)), 1);
+        }
+    }
+}
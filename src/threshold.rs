@@ -0,0 +1,298 @@
+//! Threshold (k-of-n) encryption: a file is encrypted once under a random
+//! session key, and that session key is Shamir-split across `n`
+//! recipients such that any `k` of them can reconstruct it and decrypt the
+//! body. See [`crate::shamir`] for the secret-sharing math.
+//!
+//! The on-disk format is a small container ahead of the encrypted body:
+//!
+//! ```text
+//! magic        4 bytes   b"PGPK"
+//! version      1 byte    0x01
+//! threshold    1 byte    k
+//! sym_algo     1 byte    sequoia_openpgp::types::SymmetricAlgorithm
+//! share_count  1 byte    n
+//! shares       n entries, each:
+//!                keyid         8 bytes
+//!                x             1 byte
+//!                share_len     4 bytes, little-endian
+//!                share_data    share_len bytes (an OpenPGP message
+//!                              encrypting one Shamir share to that
+//!                              recipient)
+//! body_len     8 bytes, little-endian
+//! body         body_len bytes (the SEIP-encrypted file content)
+//! ```
+
+use openpgp::cert::Cert;
+use openpgp::crypto::{KeyPair, SessionKey};
+use openpgp::packet::key::{PublicParts, UnspecifiedRole};
+use openpgp::packet::Key;
+use openpgp::parse::Parse;
+use openpgp::policy::StandardPolicy;
+use openpgp::serialize::stream::{Encryptor2, LiteralWriter, Message};
+use openpgp::types::SymmetricAlgorithm;
+use openpgp::KeyID;
+use sequoia_openpgp as openpgp;
+use std::io::{Read, Write};
+
+use crate::shamir::{self, Share};
+use crate::{EXIT_ENCRYPTION_ERROR, EXIT_KEY_ERROR};
+
+const MAGIC: &[u8; 4] = b"PGPK";
+const VERSION: u8 = 1;
+
+/// Returns whether `data` starts with the threshold-container magic.
+pub fn is_container(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+struct ShareEntry {
+    keyid: KeyID,
+    x: u8,
+    data: Vec<u8>,
+}
+
+/// Encrypts `plaintext` once under a fresh session key, Shamir-splits that
+/// key across `recipients` (one share per recipient, in order), and
+/// serializes the threshold container to `out`.
+pub fn encrypt(
+    out: &mut Vec<u8>,
+    plaintext: &[u8],
+    recipients: &[Key<PublicParts, UnspecifiedRole>],
+    threshold: u8,
+    policy: &StandardPolicy,
+) -> Result<(), (i32, String)> {
+    let sym_algo = SymmetricAlgorithm::AES256;
+    let session_key = SessionKey::new(sym_algo.key_size().map_err(|e| {
+        (EXIT_ENCRYPTION_ERROR, format!("Unsupported cipher: {}", e))
+    })?)
+    .map_err(|e| {
+        (
+            EXIT_ENCRYPTION_ERROR,
+            format!("Failed to generate session key: {}", e),
+        )
+    })?;
+
+    let mut body = Vec::new();
+    {
+        let message = Message::new(&mut body);
+        let encryptor = Encryptor2::with_session_key(message, sym_algo, session_key.clone())
+            .map_err(|e| {
+                (
+                    EXIT_ENCRYPTION_ERROR,
+                    format!("Failed to create encryptor: {}", e),
+                )
+            })?
+            .build()
+            .map_err(|e| {
+                (
+                    EXIT_ENCRYPTION_ERROR,
+                    format!("Failed to create encryptor: {}", e),
+                )
+            })?;
+        let mut literal_writer = LiteralWriter::new(encryptor).build().map_err(|e| {
+            (
+                EXIT_ENCRYPTION_ERROR,
+                format!("Failed to create writer: {}", e),
+            )
+        })?;
+        literal_writer
+            .write_all(plaintext)
+            .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to write data: {}", e)))?;
+        literal_writer.finalize().map_err(|e| {
+            (
+                EXIT_ENCRYPTION_ERROR,
+                format!("Failed to finalize encryption: {}", e),
+            )
+        })?;
+    }
+
+    let shares = shamir::split(&session_key, threshold, recipients.len() as u8)
+        .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to split session key: {}", e)))?;
+
+    let mut entries = Vec::with_capacity(shares.len());
+    for (recipient, share) in recipients.iter().zip(shares.iter()) {
+        entries.push(ShareEntry {
+            keyid: recipient.keyid(),
+            x: share.x,
+            data: encrypt_share(recipient, share)?,
+        });
+    }
+
+    write_container(out, threshold, sym_algo, &entries, &body);
+    Ok(())
+}
+
+/// PGP-encrypts one Shamir share to `recipient`.
+fn encrypt_share(
+    recipient: &Key<PublicParts, UnspecifiedRole>,
+    share: &Share,
+) -> Result<Vec<u8>, (i32, String)> {
+    let mut out = Vec::new();
+    {
+        let message = Message::new(&mut out);
+        let encryptor = Encryptor2::for_recipients(message, std::iter::once(recipient))
+            .build()
+            .map_err(|e| {
+                (
+                    EXIT_ENCRYPTION_ERROR,
+                    format!("Failed to encrypt share: {}", e),
+                )
+            })?;
+        let mut literal_writer = LiteralWriter::new(encryptor).build().map_err(|e| {
+            (
+                EXIT_ENCRYPTION_ERROR,
+                format!("Failed to encrypt share: {}", e),
+            )
+        })?;
+        literal_writer
+            .write_all(&share.y)
+            .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to encrypt share: {}", e)))?;
+        literal_writer
+            .finalize()
+            .map_err(|e| (EXIT_ENCRYPTION_ERROR, format!("Failed to encrypt share: {}", e)))?;
+    }
+    Ok(out)
+}
+
+fn write_container(
+    out: &mut Vec<u8>,
+    threshold: u8,
+    sym_algo: SymmetricAlgorithm,
+    shares: &[ShareEntry],
+    body: &[u8],
+) {
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.push(threshold);
+    out.push(u8::from(sym_algo));
+    out.push(shares.len() as u8);
+    for share in shares {
+        let id = share.keyid.as_u64().unwrap_or(0);
+        out.extend_from_slice(&id.to_be_bytes());
+        out.push(share.x);
+        out.extend_from_slice(&(share.data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&share.data);
+    }
+    out.extend_from_slice(&(body.len() as u64).to_le_bytes());
+    out.extend_from_slice(body);
+}
+
+/// A parsed threshold container, with shares still PGP-encrypted.
+pub struct Container<'a> {
+    pub threshold: u8,
+    pub sym_algo: SymmetricAlgorithm,
+    pub shares: Vec<(KeyID, u8, &'a [u8])>,
+    pub body: &'a [u8],
+}
+
+/// Parses a threshold container previously written by [`encrypt`].
+pub fn parse(data: &[u8]) -> Result<Container<'_>, (i32, String)> {
+    let bad_format = || (EXIT_ENCRYPTION_ERROR, "Malformed threshold container".to_string());
+
+    let mut cursor = data.strip_prefix(MAGIC).ok_or_else(bad_format)?;
+    let mut take = |n: usize| -> Result<&[u8], (i32, String)> {
+        if cursor.len() < n {
+            return Err(bad_format());
+        }
+        let (head, tail) = cursor.split_at(n);
+        cursor = tail;
+        Ok(head)
+    };
+
+    let version = take(1)?[0];
+    if version != VERSION {
+        return Err((
+            EXIT_ENCRYPTION_ERROR,
+            format!("Unsupported threshold container version {}", version),
+        ));
+    }
+    let threshold = take(1)?[0];
+    let sym_algo = SymmetricAlgorithm::from(take(1)?[0]);
+    let share_count = take(1)?[0];
+
+    let mut shares = Vec::with_capacity(share_count as usize);
+    for _ in 0..share_count {
+        let keyid = KeyID::new(u64::from_be_bytes(take(8)?.try_into().unwrap()));
+        let x = take(1)?[0];
+        let len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+        let data = take(len)?;
+        shares.push((keyid, x, data));
+    }
+
+    let body_len = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+    let body = take(body_len)?;
+
+    Ok(Container {
+        threshold,
+        sym_algo,
+        shares,
+        body,
+    })
+}
+
+/// Decrypts one PGP-encrypted share using `decryptor`, recovering the raw
+/// Shamir share bytes.
+pub fn decrypt_share(
+    ciphertext: &[u8],
+    decryptor: KeyPair,
+    policy: &StandardPolicy,
+) -> Result<Vec<u8>, (i32, String)> {
+    struct ShareHelper {
+        decryptor: Option<KeyPair>,
+    }
+
+    impl openpgp::parse::stream::VerificationHelper for ShareHelper {
+        fn get_certs(
+            &mut self,
+            _ids: &[openpgp::KeyHandle],
+        ) -> openpgp::Result<Vec<Cert>> {
+            Ok(Vec::new())
+        }
+        fn check(
+            &mut self,
+            _structure: openpgp::parse::stream::MessageStructure,
+        ) -> openpgp::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl openpgp::parse::stream::DecryptionHelper for ShareHelper {
+        fn decrypt<D>(
+            &mut self,
+            pkesks: &[openpgp::packet::PKESK],
+            _skesks: &[openpgp::packet::SKESK],
+            sym_algo: Option<SymmetricAlgorithm>,
+            mut decrypt: D,
+        ) -> openpgp::Result<Option<openpgp::Fingerprint>>
+        where
+            D: FnMut(SymmetricAlgorithm, &SessionKey) -> bool,
+        {
+            let mut pair = self
+                .decryptor
+                .take()
+                .ok_or_else(|| anyhow::anyhow!("share decryptor already used"))?;
+            for pkesk in pkesks {
+                if let Some((algo, sk)) = pkesk.decrypt(&mut pair, sym_algo) {
+                    if decrypt(algo, &sk) {
+                        return Ok(Some(pair.public().fingerprint()));
+                    }
+                }
+            }
+            Err(anyhow::anyhow!("Share was not encrypted to this key"))
+        }
+    }
+
+    let mut helper = ShareHelper {
+        decryptor: Some(decryptor),
+    };
+    let mut reader = openpgp::parse::stream::DecryptorBuilder::from_bytes(ciphertext)
+        .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to parse share: {}", e)))?
+        .with_policy(policy, None, &mut helper)
+        .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to decrypt share: {}", e)))?;
+
+    let mut share = Vec::new();
+    reader
+        .read_to_end(&mut share)
+        .map_err(|e| (EXIT_KEY_ERROR, format!("Failed to read share: {}", e)))?;
+    Ok(share)
+}